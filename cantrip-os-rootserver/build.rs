@@ -28,7 +28,19 @@ fn main() {
     // the system call numbering.
     let features = sel4_config::get_sel4_features(&sel4_out_dir);
     println!("features={:?}", features);
+    let printing = features.iter().any(|f| f == "CONFIG_PRINTING")
+        || env::var_os("CARGO_FEATURE_CONFIG_PRINTING").is_some();
     for feature in features {
         println!("cargo:rustc-cfg=feature=\"{}\"", feature);
     }
+
+    // The logger falls back to the in-memory ring buffer when there is no
+    // other sink (see src/logger.rs). Decide this once here so the ring,
+    // its sink and its tests all key off the same log_ring cfg.
+    let platform_write = env::var_os("CARGO_FEATURE_PLATFORM_WRITE").is_some();
+    let debug_putchar = printing && env::var_os("CARGO_FEATURE_NO_DEBUG_PUTCHAR").is_none();
+    println!("cargo:rustc-check-cfg=cfg(log_ring)");
+    if !platform_write && !debug_putchar {
+        println!("cargo:rustc-cfg=log_ring");
+    }
 }
@@ -0,0 +1,29 @@
+#
+# Copyright 2022 Google LLC
+#
+# SPDX-License-Identifier: BSD-2-Clause
+#
+
+# Fails unless ELF has an output section for each name in SECTIONS and
+# the section is not empty. Used by BuildCapDLApplication CHECK_SECTIONS
+# to verify heap_section/state_section placement survived the link.
+#
+# cmake -DOBJDUMP=<objdump> -DELF=<file> -DSECTIONS=<a;b> -P check-sections.cmake
+
+execute_process(
+    COMMAND ${OBJDUMP} -h ${ELF}
+    OUTPUT_VARIABLE headers
+    RESULT_VARIABLE result
+)
+if(NOT result EQUAL 0)
+    message(FATAL_ERROR "${OBJDUMP} -h ${ELF} failed: ${result}")
+endif()
+foreach(section IN LISTS SECTIONS)
+    # objdump -h: Idx Name Size VMA LMA File-off Algn
+    if(NOT headers MATCHES "[0-9]+ +${section} +([0-9a-f]+)")
+        message(FATAL_ERROR "${ELF}: no ${section} section; merged by the linker script?")
+    endif()
+    if(CMAKE_MATCH_1 MATCHES "^0+$")
+        message(FATAL_ERROR "${ELF}: ${section} section is empty")
+    endif()
+endforeach()
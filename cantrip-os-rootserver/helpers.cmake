@@ -6,8 +6,10 @@
 
 cmake_minimum_required(VERSION 3.7.2)
 
+set(CAPDL_ROOTSERVER_HELPERS_DIR "${CMAKE_CURRENT_LIST_DIR}" CACHE INTERNAL "")
+
 function(BuildCapDLApplication)
-    cmake_parse_arguments(PARSE_ARGV 0 CAPDL_BUILD_APP "" "C_SPEC;OUTPUT" "ELF;DEPENDS")
+    cmake_parse_arguments(PARSE_ARGV 0 CAPDL_BUILD_APP "" "C_SPEC;OUTPUT;ROOTSERVER_SIZE" "ELF;DEPENDS;CHECK_SECTIONS")
     if(NOT "${CAPDL_BUILD_APP_UNPARSED_ARGUMENTS}" STREQUAL "")
         message(FATAL_ERROR "Unknown arguments to BuildCapDLApplication")
     endif()
@@ -51,6 +53,30 @@ function(BuildCapDLApplication)
         sel4utils                # NB: for building capdl_spec.c
         capdl_loader_app_Config  # NB: for building capdl_spec.c
     )
+    # Expected rootserver size (bytes, excluding spec & archive) for the
+    # check_executable_size feature.
+    if(NOT "${CAPDL_BUILD_APP_ROOTSERVER_SIZE}" STREQUAL "")
+        set_property(
+            TARGET "${CAPDL_BUILD_APP_OUTPUT}"
+            APPEND_STRING
+            PROPERTY LINK_FLAGS " -Wl,--defsym=_capdl_rootserver_size=${CAPDL_BUILD_APP_ROOTSERVER_SIZE}"
+        )
+    endif()
+    # Sections that must be present in the linked rootserver (e.g.
+    # capdl_heap & capdl_state with the heap_section & state_section
+    # features).
+    if(NOT "${CAPDL_BUILD_APP_CHECK_SECTIONS}" STREQUAL "")
+        add_custom_command(
+            TARGET "${CAPDL_BUILD_APP_OUTPUT}"
+            POST_BUILD
+            COMMAND
+                ${CMAKE_COMMAND} -DOBJDUMP=${CMAKE_OBJDUMP}
+                -DELF=$<TARGET_FILE:${CAPDL_BUILD_APP_OUTPUT}>
+                "-DSECTIONS=${CAPDL_BUILD_APP_CHECK_SECTIONS}"
+                -P ${CAPDL_ROOTSERVER_HELPERS_DIR}/check-sections.cmake
+            VERBATIM
+        )
+    endif()
 endfunction(BuildCapDLApplication)
 
 # Hook for CAmkES build system. This allows CAmkES projects to
@@ -0,0 +1,249 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Safe access to the data linked into the rootserver image.
+//
+// Everything the rootserver consumes (capDL spec, bootinfo, component
+// archive, rootserver image) arrives through linker/runtime symbols.
+// The unsafe derefs are done once here; callers get 'static references.
+
+use cantrip_os_common::sel4_sys;
+use capdl::CDL_Model;
+#[cfg(any(test, feature = "check_executable_size"))]
+use core::fmt;
+use core::ptr;
+
+use sel4_sys::seL4_BootInfo;
+
+// Linkage to pre-calculated data used to initialize the system.
+extern "C" {
+    static capdl_spec: CDL_Model; // Generated by the CapDL tools from the .cdl spec
+
+    static __executable_start: [u8; 1]; // Start of rootserver image.
+    static _end: [u8; 1];
+
+    fn sel4runtime_bootinfo() -> *const seL4_BootInfo;
+}
+
+// Most platforms have the CAmkES components embedded in an elf segment
+// exposed through these symbols. Some platforms may store the data in
+// flash and retrieve it on demand using platform-specific methods.
+//
+// Note we depend on LTO to elide the associated elf segment when built
+// without "fill_from_cpio" (e.g. "fill_from_sec"). This is subtle but
+// allows us to leave the cmake BuildCapDLApplication function unchanged.
+#[cfg(feature = "fill_from_cpio")]
+extern "C" {
+    static _capdl_archive: [u8; 1]; // CPIO archive of component images
+    static _capdl_archive_end: [u8; 1];
+}
+
+// Optional linker-provided size of the rootserver executable, excluding
+// the capDL spec and component archive. This is an absolute symbol; its
+// address is the value (e.g. -Wl,--defsym=_capdl_rootserver_size=<bytes>,
+// see the ROOTSERVER_SIZE argument to BuildCapDLApplication).
+#[cfg(feature = "check_executable_size")]
+extern "C" {
+    static _capdl_rootserver_size: [u8; 1];
+}
+
+pub struct LinkedInputs {
+    pub spec: &'static CDL_Model,
+    pub bootinfo: &'static seL4_BootInfo,
+    pub archive: &'static [u8], // Empty when not built with "fill_from_cpio"
+    pub executable: &'static [u8],
+}
+
+fn calc_bytes(begin: *const u8, end: *const u8) -> usize {
+    (end as usize) - (begin as usize)
+}
+
+// Returns references to the linked inputs. The invariants are:
+//   - capdl_spec is generated read-only data that is never written.
+//   - sel4runtime_bootinfo returns the kernel-provided bootinfo frame
+//     which stays mapped for the life of the rootserver.
+//   - each start/end symbol pair is emitted by the linker script
+//     bracketing a single contiguous region of the rootserver image.
+pub fn linked_inputs() -> LinkedInputs {
+    let spec = unsafe { &capdl_spec };
+    let bootinfo = unsafe { &*sel4runtime_bootinfo() };
+
+    #[cfg(feature = "fill_from_cpio")]
+    let archive = unsafe {
+        core::slice::from_raw_parts(
+            ptr::addr_of!(_capdl_archive[0]),
+            calc_bytes(
+                ptr::addr_of!(_capdl_archive[0]),
+                ptr::addr_of!(_capdl_archive_end[0]),
+            ),
+        )
+    };
+    #[cfg(not(feature = "fill_from_cpio"))]
+    let archive = &[0u8; 0];
+
+    let executable = unsafe {
+        core::slice::from_raw_parts(
+            ptr::addr_of!(__executable_start[0]),
+            calc_bytes(ptr::addr_of!(__executable_start[0]), ptr::addr_of!(_end[0])),
+        )
+    };
+
+    LinkedInputs {
+        spec,
+        bootinfo,
+        archive,
+        executable,
+    }
+}
+
+// Returns the size of the rootserver proper: the image less the spec &
+// archive linked into it. None means the spec & archive do not fit in
+// the image, i.e. they are not linked inside [__executable_start, _end).
+pub fn executable_space(executable: usize, spec: usize, archive: usize) -> Option<usize> {
+    executable.checked_sub(spec.checked_add(archive)?)
+}
+
+// The computed executable size differs from _capdl_rootserver_size;
+// Display gives the warning logged at boot.
+#[cfg(any(test, feature = "check_executable_size"))]
+#[derive(Debug, PartialEq, Eq)]
+pub struct SizeMismatch {
+    pub computed: usize,
+    pub expected: usize,
+}
+#[cfg(any(test, feature = "check_executable_size"))]
+impl fmt::Display for SizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Rootserver executable size mismatch: computed {} but linker expects {}",
+            self.computed, self.expected
+        )
+    }
+}
+
+// Checks the computed executable size against _capdl_rootserver_size.
+#[cfg(feature = "check_executable_size")]
+pub fn check_executable_size(computed: usize) -> Result<(), SizeMismatch> {
+    compare_executable_size(computed, unsafe {
+        ptr::addr_of!(_capdl_rootserver_size[0]) as usize
+    })
+}
+
+#[cfg(any(test, feature = "check_executable_size"))]
+fn compare_executable_size(computed: usize, expected: usize) -> Result<(), SizeMismatch> {
+    if computed == expected {
+        Ok(())
+    } else {
+        Err(SizeMismatch { computed, expected })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::seL4_BootInfo;
+    use super::CDL_Model;
+    use super::{compare_executable_size, executable_space, linked_inputs};
+    use core::ptr;
+
+    // Stand-ins for the symbols normally provided by the capDL tools and
+    // sel4runtime. __executable_start & _end come from the host linker.
+    #[repr(transparent)]
+    struct Stub<T>(T);
+    unsafe impl<T> Sync for Stub<T> {}
+
+    #[no_mangle]
+    #[allow(non_upper_case_globals)]
+    static capdl_spec: Stub<CDL_Model> = Stub(unsafe { core::mem::zeroed() });
+
+    static BOOTINFO: Stub<seL4_BootInfo> = Stub(unsafe { core::mem::zeroed() });
+    #[no_mangle]
+    extern "C" fn sel4runtime_bootinfo() -> *const seL4_BootInfo {
+        &BOOTINFO.0
+    }
+
+    // The archive symbols must bracket a contiguous region so they are
+    // emitted together.
+    #[cfg(feature = "fill_from_cpio")]
+    const ARCHIVE_LEN: usize = 64;
+    #[cfg(feature = "fill_from_cpio")]
+    core::arch::global_asm!(
+        ".pushsection .rodata.capdl_test_archive, \"a\"",
+        ".globl _capdl_archive",
+        "_capdl_archive:",
+        ".space {len}",
+        ".globl _capdl_archive_end",
+        "_capdl_archive_end:",
+        ".popsection",
+        len = const ARCHIVE_LEN,
+    );
+
+    #[test]
+    fn spec_and_bootinfo_resolve_to_linked_symbols() {
+        let inputs = linked_inputs();
+        assert!(ptr::eq(inputs.spec, &capdl_spec.0));
+        assert!(ptr::eq(inputs.bootinfo, &BOOTINFO.0));
+    }
+
+    #[cfg(feature = "fill_from_cpio")]
+    #[test]
+    fn archive_spans_archive_symbols() {
+        assert_eq!(linked_inputs().archive.len(), ARCHIVE_LEN);
+    }
+
+    #[cfg(not(feature = "fill_from_cpio"))]
+    #[test]
+    fn archive_empty_without_cpio() {
+        assert!(linked_inputs().archive.is_empty());
+    }
+
+    #[test]
+    fn executable_contains_spec_and_archive() {
+        let inputs = linked_inputs();
+        assert!(!inputs.executable.is_empty());
+        let image = inputs.executable.as_ptr_range();
+        let spec = inputs.spec as *const CDL_Model as *const u8;
+        assert!(image.start <= spec && spec < image.end);
+        let archive = inputs.archive.as_ptr_range();
+        assert!(
+            inputs.archive.is_empty() || (image.start <= archive.start && archive.end <= image.end)
+        );
+    }
+
+    #[test]
+    fn executable_space_excludes_spec_and_archive() {
+        assert_eq!(executable_space(1000, 300, 200), Some(500));
+        assert_eq!(executable_space(500, 300, 200), Some(0));
+    }
+
+    #[test]
+    fn executable_space_underflow_is_none() {
+        assert_eq!(executable_space(400, 300, 200), None);
+        assert_eq!(executable_space(usize::MAX, usize::MAX, 1), None);
+    }
+
+    #[test]
+    fn executable_size_match_is_ok() {
+        assert_eq!(compare_executable_size(4096, 4096), Ok(()));
+    }
+
+    #[test]
+    fn executable_size_mismatch_warns() {
+        let mismatch = compare_executable_size(4096, 8192).unwrap_err();
+        assert_eq!(
+            mismatch.to_string(),
+            "Rootserver executable size mismatch: computed 4096 but linker expects 8192"
+        );
+    }
+}
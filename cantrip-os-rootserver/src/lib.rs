@@ -37,56 +37,38 @@
 // instantiated by the rootserver remain because the MemoryManager has
 // references to the capDL-specified objects.
 
-#![no_std]
-#![no_main]
+// NB: unit tests run hosted so std & the test harness main are used there.
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+
+mod boot;
+mod logger;
 
 use cantrip_os_common::allocator;
 use cantrip_os_common::sel4_sys;
 use capdl;
 use cfg_if::cfg_if;
-use core2::io::{Cursor, Write};
 use core::mem::size_of;
-use core::ptr;
 use log::*;
 use model;
 
 use capdl::CDL_Core;
-use capdl::CDL_Model;
 use capdl::CDL_ObjID;
+#[cfg(any(feature = "sparse_dup_map", feature = "no_start_threads"))]
+use capdl::CDL_ObjectType;
 use capdl::CDL_IRQ;
 
 use model::CantripOsModel;
 use model::ModelState;
 
-use sel4_sys::seL4_BootInfo;
+use boot::LinkedInputs;
+use logger::CapdlLogger;
+
 use sel4_sys::seL4_CPtr;
 use sel4_sys::seL4_CapInitThreadTCB;
 use sel4_sys::seL4_GetIPCBuffer;
 use sel4_sys::seL4_TCB_Suspend;
 
-// Linkage to pre-calculated data used to initialize the system.
-extern "C" {
-    static capdl_spec: CDL_Model; // Generated by the CapDL tools from the .cdl spec
-
-    static __executable_start: [u8; 1]; // Start of rootserver image.
-    static _end: [u8; 1];
-
-    fn sel4runtime_bootinfo() -> *const seL4_BootInfo;
-}
-
-// Most platforms have the CAmkES components embedded in an elf segment
-// exposed through these symbols. Some platforms may store the data in
-// flash and retrieve it on demand using platform-specific methods.
-//
-// Note we depend on LTO to elide the associated elf segment when built
-// without "fill_from_cpio" (e.g. "fill_from_sec"). This is subtle but
-// allows us to leave the cmake BuildCapDLApplication function unchanged.
-#[cfg(feature = "fill_from_cpio")]
-extern "C" {
-    static _capdl_archive: [u8; 1]; // CPIO archive of component images
-    static _capdl_archive_end: [u8; 1];
-}
-
 // Set log level for tracing rootseerver operation.
 cfg_if! {
     if #[cfg(feature = "LOG_DEBUG")] {
@@ -115,6 +97,10 @@ cfg_if! {
         const CONFIG_CAPDL_LOADER_MAX_OBJECTS: usize = 5500;
 
         const CONFIG_MAX_NUM_BOOTINFO_UNTYPED_CAPS: usize = 128;
+
+        // NB: the debug build has more objects but not more TCB's & CNode's
+        #[cfg(feature = "sparse_dup_map")]
+        const CONFIG_CAPDL_LOADER_MAX_DUPS: usize = 512;
     } else {
         // NB: rpi3 has 1G of memory so no need to shrink config
         // NB: max objects is ~1/2 what the C code has because we use
@@ -122,10 +108,61 @@ cfg_if! {
         //   in created ~1/2 as many capabilities.
         const CONFIG_CAPDL_LOADER_MAX_OBJECTS: usize = 10000;
         const CONFIG_MAX_NUM_BOOTINFO_UNTYPED_CAPS: usize = 230;
+
+        #[cfg(feature = "sparse_dup_map")]
+        const CONFIG_CAPDL_LOADER_MAX_DUPS: usize = CONFIG_CAPDL_LOADER_MAX_OBJECTS / 4;
     }
 }
 const CONFIG_MAX_NUM_IRQS: usize = 128;
 const CONFIG_MAX_NUM_NODES: usize = 1;
+#[cfg(feature = "no_start_threads")]
+const CONFIG_MAX_NUM_TCBS: usize = 256;
+
+// Sparse mapping from object ID to CPtr for tables where most objects
+// have no entry. Entries are kept sorted by object ID and looked up with
+// a binary search. Objects without an entry map to 0 (seL4_CapNull) like
+// the dense tables. Storage scales with N rather than the object count.
+// Only TCB's & CNode's are dup'd so the dup map can be much smaller than
+// the object tables; see CONFIG_CAPDL_LOADER_MAX_DUPS.
+#[cfg(any(test, feature = "sparse_dup_map"))]
+struct SparseCapMap<const N: usize> {
+    ids: [CDL_ObjID; N],
+    cptrs: [seL4_CPtr; N],
+    len: usize,
+}
+#[cfg(any(test, feature = "sparse_dup_map"))]
+impl<const N: usize> SparseCapMap<N> {
+    const fn new() -> Self {
+        SparseCapMap {
+            ids: [0 as CDL_ObjID; N],
+            cptrs: [0 as seL4_CPtr; N],
+            len: 0,
+        }
+    }
+    fn get(&self, obj_id: CDL_ObjID) -> seL4_CPtr {
+        match self.ids[..self.len].binary_search(&obj_id) {
+            Ok(ix) => self.cptrs[ix],
+            Err(_) => 0 as seL4_CPtr,
+        }
+    }
+    fn set(&mut self, obj_id: CDL_ObjID, slot: seL4_CPtr) {
+        match self.ids[..self.len].binary_search(&obj_id) {
+            Ok(ix) => self.cptrs[ix] = slot,
+            Err(ix) => {
+                assert!(
+                    self.len < N,
+                    "SparseCapMap full ({} entries): raise CONFIG_CAPDL_LOADER_MAX_DUPS or disable sparse_dup_map",
+                    N
+                );
+                self.ids.copy_within(ix..self.len, ix + 1);
+                self.cptrs.copy_within(ix..self.len, ix + 1);
+                self.ids[ix] = obj_id;
+                self.cptrs[ix] = slot;
+                self.len += 1;
+            }
+        }
+    }
+}
 
 // State required to process a Model specification. We separate this from
 // the implentation so callers can decide how to manage this state (and
@@ -143,7 +180,10 @@ struct CantripOsModelState {
     // Mapping from object ID to any dup of capdl_to_sel4_orig. This is
     // used to track objects as they are moved from the rootserver's CSpace
     // to the target CSpace.
+    #[cfg(not(feature = "sparse_dup_map"))]
     capdl_to_sel4_dup: [seL4_CPtr; CONFIG_CAPDL_LOADER_MAX_OBJECTS],
+    #[cfg(feature = "sparse_dup_map")]
+    capdl_to_sel4_dup: SparseCapMap<CONFIG_CAPDL_LOADER_MAX_DUPS>,
     // Mapping from IRQ number to associated handler capability.
     capdl_to_sel4_irq: [seL4_CPtr; CONFIG_MAX_NUM_IRQS],
     // Mapping from SchedCtrl number to associated scheduler context.
@@ -158,7 +198,10 @@ impl CantripOsModelState {
     pub const fn new() -> Self {
         CantripOsModelState {
             capdl_to_sel4_orig: [0 as seL4_CPtr; CONFIG_CAPDL_LOADER_MAX_OBJECTS],
+            #[cfg(not(feature = "sparse_dup_map"))]
             capdl_to_sel4_dup: [0 as seL4_CPtr; CONFIG_CAPDL_LOADER_MAX_OBJECTS],
+            #[cfg(feature = "sparse_dup_map")]
+            capdl_to_sel4_dup: SparseCapMap::new(),
             capdl_to_sel4_irq: [0 as seL4_CPtr; CONFIG_MAX_NUM_IRQS],
             capdl_to_sched_ctrl: [0 as seL4_CPtr; CONFIG_MAX_NUM_NODES],
 
@@ -187,12 +230,22 @@ impl ModelState for CantripOsModelState {
         self.capdl_to_sel4_orig[obj_id] = slot;
     }
 
+    #[cfg(not(feature = "sparse_dup_map"))]
     fn get_dup_cap(&self, obj_id: CDL_ObjID) -> seL4_CPtr {
         self.capdl_to_sel4_dup[obj_id]
     }
+    #[cfg(not(feature = "sparse_dup_map"))]
     fn set_dup_cap(&mut self, obj_id: CDL_ObjID, slot: seL4_CPtr) {
         self.capdl_to_sel4_dup[obj_id] = slot;
     }
+    #[cfg(feature = "sparse_dup_map")]
+    fn get_dup_cap(&self, obj_id: CDL_ObjID) -> seL4_CPtr {
+        self.capdl_to_sel4_dup.get(obj_id)
+    }
+    #[cfg(feature = "sparse_dup_map")]
+    fn set_dup_cap(&mut self, obj_id: CDL_ObjID, slot: seL4_CPtr) {
+        self.capdl_to_sel4_dup.set(obj_id, slot);
+    }
 
     fn get_irq_cap(&self, irq: CDL_IRQ) -> seL4_CPtr {
         self.capdl_to_sel4_irq[irq]
@@ -216,44 +269,104 @@ impl ModelState for CantripOsModelState {
     }
 }
 
-// Console output is sent through the log crate. We use seL4_DebugPutChar
-// to write to the console which only works if DEBUG_PRINTING is enabled
-// in the kernel. Note this differs from capdl-loader-app which uses
-// sel4platformsupport to write to the console/uart.
-struct CapdlLogger;
-impl log::Log for CapdlLogger  {
-    fn enabled(&self, _metadata: &Metadata) -> bool { true }
-    fn flush(&self) {}
-    fn log(&self, record: &Record) {
-        let mut buf = [0u8; 1024];
-        let mut cur =  Cursor::new(&mut buf[..]);
-        write!(&mut cur, "{}:{}", record.target(), record.args()).unwrap_or_else(|_| {
-            cur.set_position((1024 - 3) as u64);
-            cur.write(b"...").expect("write");
-        });
-        let pos = cur.position() as usize;
-
-        #[cfg(feature = "CONFIG_PRINTING")]
-        unsafe {
-            for c in &buf[..pos] {
-                let _ = sel4_sys::seL4_DebugPutChar(*c);
-            }
-            let _ = sel4_sys::seL4_DebugPutChar(b'\n');
-        }
+// A fixed-size table is too small for what bootinfo or the spec needs.
+#[derive(Debug, PartialEq, Eq)]
+struct CapacityExceeded {
+    needed: usize,
+    capacity: usize,
+}
+
+// Checks needed entries fit in a table of capacity entries. Used to fail
+// before any syscalls rather than part way through init_system. Nothing
+// is dropped to make things fit (e.g. untypeds are not tracked
+// largest-first); the caller just reports the error.
+fn check_capacity(needed: usize, capacity: usize) -> Result<(), CapacityExceeded> {
+    if needed <= capacity {
+        Ok(())
+    } else {
+        Err(CapacityExceeded { needed, capacity })
     }
 }
 
+// Returns the number of spec objects that may be dup'd (TCB's & CNode's);
+// the dup map must have an entry for each.
+#[cfg(feature = "sparse_dup_map")]
+fn count_dup_objects(spec: &capdl::CDL_Model) -> usize {
+    spec.obj_slice()
+        .iter()
+        .filter(|obj| {
+            matches!(
+                obj.r#type(),
+                CDL_ObjectType::CDL_TCB | CDL_ObjectType::CDL_CNode
+            )
+        })
+        .count()
+}
+
+// Returns the object ID's of the spec's TCB's.
+#[cfg(feature = "no_start_threads")]
+fn tcb_ids(spec: &capdl::CDL_Model) -> impl Iterator<Item = CDL_ObjID> + '_ {
+    spec.obj_slice()
+        .iter()
+        .enumerate()
+        .filter(|(_, obj)| matches!(obj.r#type(), CDL_ObjectType::CDL_TCB))
+        .map(|(obj_id, _)| obj_id)
+}
+
+// TCB's left suspended by "no_start_threads"; exported so a test harness
+// can find and resume them. Entries [0, len) are valid. Each cptr is the
+// TCB's original cap, a slot in the rootserver's CNode.
+#[cfg(any(test, feature = "no_start_threads"))]
+#[repr(C)]
+struct SuspendedTcbs<const N: usize> {
+    len: usize,
+    obj_ids: [CDL_ObjID; N],
+    cptrs: [seL4_CPtr; N],
+}
+#[cfg(any(test, feature = "no_start_threads"))]
+impl<const N: usize> SuspendedTcbs<N> {
+    const fn new() -> Self {
+        SuspendedTcbs {
+            len: 0,
+            obj_ids: [0 as CDL_ObjID; N],
+            cptrs: [0 as seL4_CPtr; N],
+        }
+    }
+    fn record(&mut self, tcbs: impl Iterator<Item = CDL_ObjID>, state: &impl ModelState) {
+        for obj_id in tcbs {
+            assert!(self.len < N, "SuspendedTcbs full ({} entries)", N);
+            self.obj_ids[self.len] = obj_id;
+            self.cptrs[self.len] = state.get_orig_cap(obj_id);
+            self.len += 1;
+        }
+    }
+}
+#[cfg(feature = "no_start_threads")]
 #[no_mangle]
+static mut CAPDL_SUSPENDED_TCBS: SuspendedTcbs<CONFIG_MAX_NUM_TCBS> = SuspendedTcbs::new();
+
+// NB: the "heap_section" & "state_section" features place these in the
+//   capdl_heap & capdl_state sections so a platform linker script can
+//   locate them (e.g. in fast SRAM). The names are C identifiers so an
+//   unmatched section stays a separate output section bracketed by
+//   __start_<name>/__stop_<name> instead of being merged by a .bss.* rule.
+#[cfg_attr(feature = "heap_section", link_section = "capdl_heap")]
+static mut HEAP_MEMORY: [u8; 4096] = [0; 4096];
+// NB: STATE does not fit on the stack or heap.
+#[cfg_attr(feature = "state_section", link_section = "capdl_state")]
+static mut STATE: CantripOsModelState = CantripOsModelState::new();
+
+#[cfg_attr(not(test), no_mangle)]
 pub fn main() {
     // Setup logger.
     static CAPDL_LOGGER: CapdlLogger = CapdlLogger;
     log::set_logger(&CAPDL_LOGGER).unwrap();
     log::set_max_level(INIT_LOG_LEVEL);
+    info!("Log sink: {}", logger::log_sink());
 
     // Setup memory allocation from a fixed heap. For the configurations
     // tested no heap was used. CantripOsModel may use the heap if the model
     // has many VSpace roots.
-    static mut HEAP_MEMORY: [u8; 4096] = [0; 4096];
     unsafe {
         allocator::ALLOCATOR.init(HEAP_MEMORY.as_mut_ptr(), HEAP_MEMORY.len());
         trace!(
@@ -263,8 +376,12 @@ pub fn main() {
         );
     }
 
-    let capdl_spec_ref = unsafe { &capdl_spec };
-    let bootinfo_ref = unsafe { &*sel4runtime_bootinfo() };
+    let LinkedInputs {
+        spec: capdl_spec_ref,
+        bootinfo: bootinfo_ref,
+        archive: capdl_archive_ref,
+        executable: executable_ref,
+    } = boot::linked_inputs();
 
     // Verify the IPC buffer is setup correctly for system calls. In
     // particular we need Rust's tls-model to match what the kernel uses.
@@ -299,30 +416,34 @@ pub fn main() {
         bootinfo_ref.empty.end - bootinfo_ref.empty.start,
         CONFIG_CAPDL_LOADER_MAX_OBJECTS
     );
-
-    fn calc_bytes(begin: *const u8, end: *const u8) -> usize {
-        (end as usize) - (begin as usize)
-    }
-
-    #[cfg(feature = "fill_from_cpio")]
-    let capdl_archive_ref = unsafe {
-        core::slice::from_raw_parts(
-            ptr::addr_of!(_capdl_archive[0]),
-            calc_bytes(
-                ptr::addr_of!(_capdl_archive[0]),
-                ptr::addr_of!(_capdl_archive_end[0]),
-            ),
-        )
-    };
-    #[cfg(not(feature = "fill_from_cpio"))]
-    let capdl_archive_ref = &[0u8; 0];
-
-    let executable_ref = unsafe {
-        core::slice::from_raw_parts(
-            ptr::addr_of!(__executable_start[0]),
-            calc_bytes(ptr::addr_of!(__executable_start[0]), ptr::addr_of!(_end[0])),
-        )
-    };
+    // NB: a full dup map would otherwise panic mid-init_system with
+    //   objects already created.
+    #[cfg(feature = "sparse_dup_map")]
+    if let Err(e) = check_capacity(
+        count_dup_objects(capdl_spec_ref),
+        CONFIG_CAPDL_LOADER_MAX_DUPS,
+    ) {
+        panic!(
+            "Not enough dup storage: spec has {} TCB's & CNode's but CONFIG_CAPDL_LOADER_MAX_DUPS={}",
+            e.needed, e.capacity
+        );
+    }
+    #[cfg(feature = "no_start_threads")]
+    if let Err(e) = check_capacity(tcb_ids(capdl_spec_ref).count(), CONFIG_MAX_NUM_TCBS) {
+        panic!(
+            "Not enough suspended TCB storage: spec has {} TCB's but CONFIG_MAX_NUM_TCBS={}",
+            e.needed, e.capacity
+        );
+    }
+    if let Err(e) = check_capacity(
+        bootinfo_ref.untyped.end - bootinfo_ref.untyped.start,
+        CONFIG_MAX_NUM_BOOTINFO_UNTYPED_CAPS,
+    ) {
+        panic!(
+            "Not enough untyped storage: bootinfo has {} but CONFIG_MAX_NUM_BOOTINFO_UNTYPED_CAPS={}",
+            e.needed, e.capacity
+        );
+    }
 
     fn to_megabytes(bytes: usize) -> f32 {
         bytes as f32 / (1024. * 1024.)
@@ -333,10 +454,25 @@ pub fn main() {
         "CAmkES components: {:.2} Mbytes",
         to_megabytes(capdl_archive_ref.len())
     );
-    info!(
-        "Rootserver executable: {:.2} Mbytes",
-        to_megabytes(executable_ref.len() - (capdl_space + capdl_archive_ref.len()))
-    );
+    let executable_space =
+        boot::executable_space(executable_ref.len(), capdl_space, capdl_archive_ref.len());
+    // The calculation assumes the spec & archive are linked inside
+    // [__executable_start, _end).
+    match executable_space {
+        Some(space) => {
+            info!("Rootserver executable: {:.2} Mbytes", to_megabytes(space));
+            #[cfg(feature = "check_executable_size")]
+            if let Err(mismatch) = boot::check_executable_size(space) {
+                warn!("{}", mismatch);
+            }
+        }
+        // NB: no size check; the computed size is meaningless.
+        None => warn!(
+            "Rootserver executable: spec + archive ({} bytes) exceed the image ({} bytes)",
+            capdl_space + capdl_archive_ref.len(),
+            executable_ref.len()
+        ),
+    }
 
     // The model goes on the stack which usually has a fixed & limited size.
     // We don't know what's been configured but the default is 16KB; require
@@ -345,8 +481,6 @@ pub fn main() {
     // size mostly depends on how space is given to vspace_roots.
     assert!(size_of::<CantripOsModel>() < (16 * 1024 / 2));
 
-    // NB: STATE does not fit on the stack or heap.
-    static mut STATE: CantripOsModelState = CantripOsModelState::new();
     let mut model = CantripOsModel::new(
         unsafe { &mut STATE },
         capdl_spec_ref,
@@ -380,7 +514,146 @@ pub fn main() {
     //   will cause the rootserver's memory to be returned to the free pool.
     model.handoff_capabilities().expect("handoff_capabilities");
 
+    // A test harness may want the system fully constructed but with no
+    // threads running so it can inspect state and resume selectively.
+    #[cfg(feature = "no_start_threads")]
+    {
+        let tcbs = unsafe { &mut *core::ptr::addr_of_mut!(CAPDL_SUSPENDED_TCBS) };
+        tcbs.record(tcb_ids(capdl_spec_ref), unsafe { &STATE });
+        info!(
+            "Skipping start_threads; {} threads left suspended (see CAPDL_SUSPENDED_TCBS)",
+            tcbs.len
+        );
+    }
+    #[cfg(not(feature = "no_start_threads"))]
     model.start_threads().expect("start_threads");
 
     let _ = unsafe { seL4_TCB_Suspend(seL4_CapInitThreadTCB) };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untypeds_at_limit_fit() {
+        let max = CONFIG_MAX_NUM_BOOTINFO_UNTYPED_CAPS;
+        assert_eq!(check_capacity(0, max), Ok(()));
+        assert_eq!(check_capacity(max, max), Ok(()));
+    }
+
+    #[test]
+    fn untypeds_over_limit_fail() {
+        let max = CONFIG_MAX_NUM_BOOTINFO_UNTYPED_CAPS;
+        assert_eq!(
+            check_capacity(max + 1, max),
+            Err(CapacityExceeded {
+                needed: max + 1,
+                capacity: max,
+            })
+        );
+    }
+
+    #[test]
+    fn suspended_tcbs_record_orig_caps() {
+        let mut state = Box::new(CantripOsModelState::new());
+        state.set_orig_cap(3, 30);
+        state.set_orig_cap(7, 70);
+        state.set_orig_cap(9, 90); // Not a TCB
+        let mut tcbs = SuspendedTcbs::<4>::new();
+        tcbs.record([3, 7].into_iter(), &*state);
+        assert_eq!(tcbs.len, 2);
+        assert_eq!(&tcbs.obj_ids[..tcbs.len], &[3, 7]);
+        assert_eq!(&tcbs.cptrs[..tcbs.len], &[30, 70]);
+    }
+
+    #[test]
+    #[should_panic(expected = "SuspendedTcbs full")]
+    fn suspended_tcbs_full_asserts() {
+        let state = Box::new(CantripOsModelState::new());
+        let mut tcbs = SuspendedTcbs::<1>::new();
+        tcbs.record([1, 2].into_iter(), &*state);
+    }
+
+    #[test]
+    fn sparse_unset_is_null() {
+        let map = SparseCapMap::<4>::new();
+        assert_eq!(map.get(0), 0);
+        assert_eq!(map.get(123), 0);
+    }
+
+    #[test]
+    fn sparse_overwrite_keeps_one_entry() {
+        let mut map = SparseCapMap::<4>::new();
+        map.set(7, 100);
+        map.set(7, 200);
+        assert_eq!(map.get(7), 200);
+        assert_eq!(map.len, 1);
+    }
+
+    #[test]
+    fn sparse_out_of_order_inserts() {
+        let mut map = SparseCapMap::<8>::new();
+        for (obj_id, slot) in [(50, 5), (10, 1), (30, 3), (20, 2), (40, 4)] {
+            map.set(obj_id, slot);
+        }
+        assert_eq!(&map.ids[..map.len], &[10, 20, 30, 40, 50]);
+        for (obj_id, slot) in [(10, 1), (20, 2), (30, 3), (40, 4), (50, 5)] {
+            assert_eq!(map.get(obj_id), slot);
+        }
+        assert_eq!(map.get(25), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "sparse_dup_map")]
+    fn sparse_full_map_asserts() {
+        let mut map = SparseCapMap::<2>::new();
+        map.set(1, 1);
+        map.set(2, 2);
+        map.set(1, 3); // Overwrite still fits
+        map.set(3, 3);
+    }
+
+    #[test]
+    fn sparse_storage_scales_with_capacity() {
+        let entry = size_of::<CDL_ObjID>() + size_of::<seL4_CPtr>();
+        assert_eq!(
+            size_of::<SparseCapMap<64>>() - size_of::<SparseCapMap<32>>(),
+            32 * entry
+        );
+        assert!(
+            size_of::<SparseCapMap<{ CONFIG_CAPDL_LOADER_MAX_OBJECTS / 4 }>>()
+                < size_of::<[seL4_CPtr; CONFIG_CAPDL_LOADER_MAX_OBJECTS]>()
+        );
+    }
+
+    // The linker defines __start_<name>/__stop_<name> for sections named
+    // like C identifiers; check the statics land inside them.
+    #[cfg(feature = "heap_section")]
+    #[test]
+    fn heap_is_in_capdl_heap_section() {
+        extern "C" {
+            static __start_capdl_heap: [u8; 1];
+            static __stop_capdl_heap: [u8; 1];
+        }
+        let heap = unsafe { core::ptr::addr_of!(HEAP_MEMORY) };
+        let start = unsafe { core::ptr::addr_of!(__start_capdl_heap[0]) } as usize;
+        let stop = unsafe { core::ptr::addr_of!(__stop_capdl_heap[0]) } as usize;
+        assert!(start <= heap as usize && heap as usize + size_of::<[u8; 4096]>() <= stop);
+    }
+
+    #[cfg(feature = "state_section")]
+    #[test]
+    fn state_is_in_capdl_state_section() {
+        extern "C" {
+            static __start_capdl_state: [u8; 1];
+            static __stop_capdl_state: [u8; 1];
+        }
+        let state = unsafe { core::ptr::addr_of!(STATE) };
+        let start = unsafe { core::ptr::addr_of!(__start_capdl_state[0]) } as usize;
+        let stop = unsafe { core::ptr::addr_of!(__stop_capdl_state[0]) } as usize;
+        assert!(
+            start <= state as usize && state as usize + size_of::<CantripOsModelState>() <= stop
+        );
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Console output is sent through the log crate. Each record is formatted
+// into a LogBuf and written to one sink, chosen at build time:
+//   - platform_write: the whole record goes to a function the platform
+//     links in (e.g. for UART DMA),
+//   - seL4_DebugPutChar: used when the kernel has CONFIG_PRINTING, unless
+//     "no_debug_putchar" is set for kernels that lack it,
+//   - otherwise an in-memory ring buffer (CAPDL_LOG_RING) that can be
+//     recovered with a debugger or memory dump.
+// A platform may also set CAPDL_PUTCHAR_HOOK at runtime, which overrides
+// the build-time sink. seL4_DebugPutChar is only referenced when it is
+// the selected sink. See README.md for the platform contracts. Note this
+// differs from capdl-loader-app which uses sel4platformsupport to write
+// to the console/uart.
+
+use cfg_if::cfg_if;
+use core2::io::{Cursor, Write};
+use log::{Metadata, Record};
+
+// Circular byte buffer; head is the next byte to write and wrapped is set
+// once the buffer has filled, after which the oldest byte is at head.
+// The layout is fixed so the buffer can be decoded from a memory dump.
+// NB: build.rs sets log_ring when the ring buffer is the default sink.
+#[cfg(any(test, log_ring))]
+mod ring {
+    pub const LOG_RING_SIZE: usize = 4096;
+
+    #[repr(C)]
+    pub struct LogRing {
+        pub buf: [u8; LOG_RING_SIZE],
+        pub head: usize,
+        pub wrapped: bool,
+    }
+    impl LogRing {
+        pub const fn new() -> Self {
+            LogRing {
+                buf: [0u8; LOG_RING_SIZE],
+                head: 0,
+                wrapped: false,
+            }
+        }
+        pub fn push(&mut self, data: &[u8]) {
+            for &c in data {
+                self.buf[self.head] = c;
+                self.head += 1;
+                if self.head == self.buf.len() {
+                    self.head = 0;
+                    self.wrapped = true;
+                }
+            }
+        }
+    }
+}
+
+// Select the default log sink; default_emit writes a formatted record
+// (incl. newline).
+cfg_if! {
+    if #[cfg(log_ring)] {
+        use ring::LogRing;
+        const DEFAULT_SINK: &str = "ring buffer";
+        // NB: the rootserver is single-threaded so no locking is needed.
+        #[no_mangle]
+        static mut CAPDL_LOG_RING: LogRing = LogRing::new();
+        fn default_emit(data: &[u8]) {
+            unsafe { (*core::ptr::addr_of_mut!(CAPDL_LOG_RING)).push(data) };
+        }
+    } else if #[cfg(feature = "platform_write")] {
+        extern "C" {
+            fn platform_write(data: *const u8, len: usize);
+        }
+        const DEFAULT_SINK: &str = "platform_write";
+        // NB: data is the record in LogBuf so the platform can hand it
+        //   directly to UART DMA (see LOG_BUF_ALIGN).
+        fn default_emit(data: &[u8]) {
+            unsafe { platform_write(data.as_ptr(), data.len()) };
+        }
+    } else {
+        use cantrip_os_common::sel4_sys;
+        const DEFAULT_SINK: &str = "seL4_DebugPutChar";
+        fn default_emit(data: &[u8]) {
+            for &c in data {
+                let _ = unsafe { sel4_sys::seL4_DebugPutChar(c) };
+            }
+        }
+    }
+}
+
+// Per-byte console function a platform can install at runtime, e.g. from
+// a constructor that runs before main. When set it replaces the default
+// sink so the rootserver links whether or not the platform provides one.
+// NB: Option<fn> has the layout of a nullable C function pointer.
+#[no_mangle]
+static mut CAPDL_PUTCHAR_HOOK: Option<unsafe extern "C" fn(u8)> = None;
+
+fn putchar_hook() -> Option<unsafe extern "C" fn(u8)> {
+    unsafe { *core::ptr::addr_of!(CAPDL_PUTCHAR_HOOK) }
+}
+
+// Returns the name of the sink log records currently go to.
+pub fn log_sink() -> &'static str {
+    if putchar_hook().is_some() {
+        "CAPDL_PUTCHAR_HOOK"
+    } else {
+        DEFAULT_SINK
+    }
+}
+
+fn emit(data: &[u8]) {
+    match putchar_hook() {
+        Some(putchar) => {
+            for &c in data {
+                unsafe { putchar(c) };
+            }
+        }
+        None => default_emit(data),
+    }
+}
+
+const LOG_BUF_SIZE: usize = 1024;
+
+// Staging buffer for formatting a record. The platform_write sink is
+// passed the buffer itself; UART DMA engines may require it be cache-line
+// aligned, enable "log_buf_align_64" or "log_buf_align_128" to match the
+// platform's cache line without a bounce buffer.
+// NB: repr(align) takes a literal so each alignment is its own feature.
+cfg_if! {
+    if #[cfg(feature = "log_buf_align_128")] {
+        const LOG_BUF_ALIGN: usize = 128;
+        #[repr(C, align(128))]
+        struct LogBuf([u8; LOG_BUF_SIZE]);
+    } else if #[cfg(feature = "log_buf_align_64")] {
+        const LOG_BUF_ALIGN: usize = 64;
+        #[repr(C, align(64))]
+        struct LogBuf([u8; LOG_BUF_SIZE]);
+    } else {
+        const LOG_BUF_ALIGN: usize = 1;
+        struct LogBuf([u8; LOG_BUF_SIZE]);
+    }
+}
+static_assertions::const_assert_eq!(core::mem::align_of::<LogBuf>(), LOG_BUF_ALIGN);
+
+// Formats record into buf followed by a newline and returns the length.
+// Records that do not fit are truncated with "...".
+fn format_record(buf: &mut LogBuf, record: &Record) -> usize {
+    let mut cur = Cursor::new(&mut buf.0[..LOG_BUF_SIZE - 1]); // Space for '\n'
+    write!(&mut cur, "{}:{}", record.target(), record.args()).unwrap_or_else(|_| {
+        cur.set_position((LOG_BUF_SIZE - 1 - 3) as u64);
+        cur.write(b"...").expect("write");
+    });
+    let pos = cur.position() as usize;
+    buf.0[pos] = b'\n';
+    pos + 1
+}
+
+pub struct CapdlLogger;
+impl log::Log for CapdlLogger  {
+    fn enabled(&self, _metadata: &Metadata) -> bool { true }
+    fn flush(&self) {}
+    fn log(&self, record: &Record) {
+        let mut buf = LogBuf([0u8; LOG_BUF_SIZE]);
+        let len = format_record(&mut buf, record);
+        emit(&buf.0[..len]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ring::{LogRing, LOG_RING_SIZE};
+    use super::*;
+    use log::Log;
+    use std::sync::Mutex;
+
+    // Serializes tests that use the global sink state.
+    static SINK_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn ring_push_appends() {
+        let mut ring = LogRing::new();
+        ring.push(b"abc");
+        assert_eq!(&ring.buf[..3], b"abc");
+        assert_eq!(ring.head, 3);
+        assert!(!ring.wrapped);
+    }
+
+    #[test]
+    fn ring_exact_fill_wraps_to_start() {
+        let mut ring = LogRing::new();
+        ring.push(&[b'a'; LOG_RING_SIZE]);
+        assert_eq!(ring.head, 0);
+        assert!(ring.wrapped);
+    }
+
+    #[test]
+    fn ring_overwrites_oldest() {
+        let mut ring = LogRing::new();
+        ring.push(&[b'a'; LOG_RING_SIZE - 1]);
+        assert!(!ring.wrapped);
+        ring.push(b"xyz");
+        assert!(ring.wrapped);
+        assert_eq!(ring.head, 2);
+        assert_eq!(ring.buf[LOG_RING_SIZE - 1], b'x');
+        assert_eq!(&ring.buf[..2], b"yz");
+        assert_eq!(ring.buf[ring.head], b'a'); // Oldest surviving byte
+    }
+
+    #[test]
+    fn format_appends_newline() {
+        let mut buf = LogBuf([0u8; LOG_BUF_SIZE]);
+        let len = format_record(
+            &mut buf,
+            &Record::builder()
+                .target("t")
+                .args(format_args!("x"))
+                .build(),
+        );
+        assert_eq!(&buf.0[..len], b"t:x\n");
+    }
+
+    #[test]
+    fn format_truncates_long_records() {
+        let long = [b'z'; 2 * LOG_BUF_SIZE];
+        let long = core::str::from_utf8(&long).unwrap();
+        let mut buf = LogBuf([0u8; LOG_BUF_SIZE]);
+        let len = format_record(
+            &mut buf,
+            &Record::builder()
+                .target("t")
+                .args(format_args!("{}", long))
+                .build(),
+        );
+        assert_eq!(len, LOG_BUF_SIZE);
+        assert!(buf.0.ends_with(b"...\n"));
+    }
+
+    #[test]
+    fn log_buf_meets_configured_alignment() {
+        let buf = LogBuf([0u8; LOG_BUF_SIZE]);
+        assert_eq!(buf.0.as_ptr() as usize % LOG_BUF_ALIGN, 0);
+        if cfg!(feature = "log_buf_align_128") {
+            assert_eq!(LOG_BUF_ALIGN, 128);
+        } else if cfg!(feature = "log_buf_align_64") {
+            assert_eq!(LOG_BUF_ALIGN, 64);
+        }
+    }
+
+    #[cfg(log_ring)]
+    #[test]
+    fn records_go_to_ring_without_printing() {
+        let _guard = SINK_LOCK.lock().unwrap();
+        assert_eq!(log_sink(), "ring buffer");
+        CapdlLogger.log(
+            &Record::builder()
+                .target("test")
+                .args(format_args!("hello {}", 42))
+                .build(),
+        );
+        let ring = unsafe { &*core::ptr::addr_of!(CAPDL_LOG_RING) };
+        assert!(!ring.wrapped);
+        assert!(ring.buf[..ring.head].ends_with(b"test:hello 42\n"));
+    }
+
+    static HOOKED: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+    unsafe extern "C" fn test_putchar(c: u8) {
+        HOOKED.lock().unwrap().push(c);
+    }
+
+    #[test]
+    fn putchar_hook_overrides_default_sink() {
+        let _guard = SINK_LOCK.lock().unwrap();
+        unsafe { *core::ptr::addr_of_mut!(CAPDL_PUTCHAR_HOOK) = Some(test_putchar) };
+        assert_eq!(log_sink(), "CAPDL_PUTCHAR_HOOK");
+        CapdlLogger.log(
+            &Record::builder()
+                .target("test")
+                .args(format_args!("hooked"))
+                .build(),
+        );
+        unsafe { *core::ptr::addr_of_mut!(CAPDL_PUTCHAR_HOOK) = None };
+        assert_eq!(log_sink(), DEFAULT_SINK);
+        assert_eq!(&*HOOKED.lock().unwrap(), b"test:hooked\n");
+    }
+}